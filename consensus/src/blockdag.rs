@@ -1,20 +1,35 @@
+use anyhow::{bail, ensure};
 use consensus_types::{
     blockhash::{BlockHashes, KType, ORIGIN},
     header::{ConsensusHeader, Header},
 };
+use database::absent_ancestor::{
+    AbsentAncestorStore, AbsentAncestorStoreReader, DbAbsentAncestorStore,
+};
+use database::cache::{CacheCapacities, LruCache};
 use database::consensus::{
     DbGhostdagStore, DbHeadersStore, DbReachabilityStore, DbRelationsStore, GhostdagStore,
     HeaderStore, ReachabilityStoreReader, RelationsStore, RelationsStoreReader,
 };
 use database::prelude::FlexiDagStorage;
+use database::prelude::StoreError;
+use database::sync_accumulator::DbSyncDagAccumulator;
 use ghostdag::protocol::GhostdagManager;
+use ghostdag::types::GhostdagData;
 use parking_lot::RwLock;
-use reachability::{inquirer, reachability_service::MTReachabilityService};
+use reachability::{
+    errors::ReachabilityError, inquirer, reachability_service::MTReachabilityService,
+};
 use starcoin_crypto::HashValue as Hash;
-use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 
+use crate::pruning::PruningPointManager;
+
+/// Default number of blocks a tip's selected-parent chain must advance past the pruning
+/// point before history below it is pruned, used when a caller doesn't override it.
+pub const DEFAULT_PRUNING_DEPTH: u64 = 100_000;
+
 pub type DbGhostdagManager = GhostdagManager<
     DbGhostdagStore,
     DbRelationsStore,
@@ -26,17 +41,71 @@ pub struct BlockDAG {
     ghostdag_manager: DbGhostdagManager,
     relations_store: DbRelationsStore,
     reachability_store: DbReachabilityStore,
+    reachability_service: MTReachabilityService<DbReachabilityStore>,
     ghostdag_store: DbGhostdagStore,
     header_store: DbHeadersStore,
-    /// orphan blocks, parent hash -> orphan block
-    missing_blocks: HashMap<Hash, HashSet<Header>>,
+    /// orphan blocks, keyed by the missing parent hash they are waiting on. Backed by RocksDB
+    /// so buffered orphans survive a restart mid-sync.
+    absent_ancestor_store: DbAbsentAncestorStore,
+    /// Merkle accumulator over every block committed to the DAG, in GHOSTDAG topological order.
+    /// Lets a syncing peer request a verifiable, contiguous slice of DAG history.
+    sync_accumulator: DbSyncDagAccumulator,
+    /// current leaf set of the DAG, i.e. headers that are not yet an ancestor of any other
+    /// known header
+    tips: HashSet<Hash>,
+    pruning_manager: PruningPointManager,
+    /// Read caches sitting in front of `ghostdag_store`/`header_store`/`reachability_service`,
+    /// the stores that dominate read amplification during DAG traversal (`ghostdag()`,
+    /// `check_ancestor_of`, orphan draining). `ghostdag_cache` is shared (via `Arc`) with
+    /// `PruningPointManager`, which walks the same `ghostdag_store` keys while following the
+    /// selected-parent chain back from a tip, so a hash either side populates is warm for the
+    /// other. `relations_store`'s equivalent hot path (`get_parents` during pruning) has its own
+    /// cache inside `PruningPointManager` instead, since that's the only place it's walked
+    /// repeatedly.
+    ghostdag_cache: Arc<LruCache<Hash, Arc<GhostdagData>>>,
+    in_dag_cache: LruCache<Hash, bool>,
+    ancestor_cache: LruCache<(Hash, Hash), bool>,
+    /// Every hash already appended to `sync_accumulator`, so `emit_to_sync_accumulator`'s
+    /// recursion into already-committed ancestors is a cheap no-op instead of re-appending them.
+    accumulator_emitted: HashSet<Hash>,
 }
 
 impl BlockDAG {
     pub fn new(genesis: Header, k: KType, db: FlexiDagStorage) -> Self {
+        Self::new_with_pruning_depth(genesis, k, db, DEFAULT_PRUNING_DEPTH)
+    }
+
+    /// Like `new`, but with an explicit pruning retention depth (in blocks of selected-parent
+    /// chain) instead of `DEFAULT_PRUNING_DEPTH`.
+    pub fn new_with_pruning_depth(
+        genesis: Header,
+        k: KType,
+        db: FlexiDagStorage,
+        pruning_depth: u64,
+    ) -> Self {
+        Self::new_with_config(
+            genesis,
+            k,
+            db,
+            pruning_depth,
+            CacheCapacities::default(),
+        )
+    }
+
+    /// Like `new_with_pruning_depth`, but with explicit read-cache capacities instead of
+    /// `CacheCapacities::default()`.
+    pub fn new_with_config(
+        genesis: Header,
+        k: KType,
+        db: FlexiDagStorage,
+        pruning_depth: u64,
+        cache_capacities: CacheCapacities,
+    ) -> Self {
         let ghostdag_store = db.ghost_dag_store.clone();
         let header_store = db.header_store.clone();
         let relations_store = db.relations_store.clone();
+        let absent_ancestor_store = db.absent_ancestor_store.clone();
+        let sync_accumulator = db.sync_accumulator.clone();
         let mut reachability_store = db.reachability_store;
         inquirer::init(&mut reachability_store).unwrap();
         let reachability_service =
@@ -47,7 +116,24 @@ impl BlockDAG {
             ghostdag_store.clone(),
             relations_store.clone(),
             header_store.clone(),
-            reachability_service,
+            reachability_service.clone(),
+        );
+        // `sync_accumulator` resumes its persisted leaves, so seed the emitted set from them.
+        let accumulator_emitted = sync_accumulator
+            .get_leaf_range(0, sync_accumulator.num_leaves())
+            .expect("failed to load sync accumulator leaves")
+            .into_iter()
+            .collect();
+        let ghostdag_cache = Arc::new(LruCache::new(cache_capacities.ghostdag));
+        let pruning_manager = PruningPointManager::new(
+            ghostdag_store.clone(),
+            header_store.clone(),
+            relations_store.clone(),
+            reachability_store.clone(),
+            Hash::new(ORIGIN),
+            pruning_depth,
+            cache_capacities,
+            ghostdag_cache.clone(),
         );
 
         let mut dag = Self {
@@ -55,9 +141,17 @@ impl BlockDAG {
             ghostdag_manager,
             relations_store,
             reachability_store,
+            reachability_service,
             ghostdag_store,
             header_store,
-            missing_blocks: HashMap::new(),
+            absent_ancestor_store,
+            sync_accumulator,
+            tips: HashSet::new(),
+            pruning_manager,
+            ghostdag_cache,
+            in_dag_cache: LruCache::new(cache_capacities.header),
+            ancestor_cache: LruCache::new(cache_capacities.reachability),
+            accumulator_emitted,
         };
         dag.init_with_genesis();
         dag
@@ -71,82 +165,427 @@ impl BlockDAG {
             .insert(Hash::new(ORIGIN), BlockHashes::new(vec![]))
             .unwrap();
         self.commit_header(&self.genesis.clone())
+            .expect("genesis must commit cleanly")
     }
 
-    pub fn commit_header(&mut self, header: &Header) {
-        // Generate ghostdag data
+    /// Commit `header` to the DAG stores, returning an error instead of panicking when a
+    /// store operation fails. Two error paths are handled specially so sync/reorg never
+    /// crashes the node:
+    /// - a reachability `DataInconsistency` resets the reindex root to the DAG origin and
+    ///   bails with the offending header, so the caller can retry the commit;
+    /// - `header_store` already holding this header means it was already fully committed (e.g.
+    ///   during a replayed sync) and is treated as a no-op success.
+    ///
+    /// `header_store` is written last and is the single source of truth for "fully committed",
+    /// so the idempotency short-circuit below checks it instead of an earlier store: that way a
+    /// commit that bailed out partway through (e.g. on the `DataInconsistency` path) is never
+    /// mistaken for done, and a retry picks up exactly where the previous attempt left off.
+    pub fn commit_header(&mut self, header: &Header) -> anyhow::Result<()> {
+        if self.header_store.has(header.hash())? {
+            return Ok(());
+        }
 
+        // Generate ghostdag data
         let parents_hash = header.parents_hash();
         let ghostdag_data = if header.hash() != self.genesis.hash() {
             self.ghostdag_manager.ghostdag(parents_hash)
         } else {
             self.ghostdag_manager.genesis_ghostdag_data()
         };
-        // Store ghostdata
-        self.ghostdag_store
-            .insert(header.hash(), Arc::new(ghostdag_data.clone()))
-            .unwrap();
 
-        // Update reachability store
+        // Update reachability store first: this is the only step that can fail with
+        // `DataInconsistency`, and unlike the store writes below it doesn't leave anything
+        // behind to roll back, so a retry after the bail starts from a clean slate.
         let mut reachability_store = self.reachability_store.clone();
         let mut merge_set = ghostdag_data
             .unordered_mergeset_without_selected_parent()
             .filter(|hash| self.reachability_store.has(*hash).unwrap());
 
-        inquirer::add_block(
+        match inquirer::add_block(
             &mut reachability_store,
             header.hash(),
             ghostdag_data.selected_parent,
             &mut merge_set,
-        )
-        .unwrap();
+        ) {
+            Ok(()) => {}
+            Err(ReachabilityError::DataInconsistency) => {
+                self.set_reindex_root(Hash::new(ORIGIN))?;
+                bail!(
+                    "reachability data inconsistency while committing {}, reindex root was reset",
+                    header.hash()
+                );
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        // Store ghostdata. `KeyAlreadyExists` here just means a previous attempt got this far
+        // before failing later on; it does not mean the header is fully committed (see above),
+        // so we keep going instead of returning early.
+        let ghostdag_data = Arc::new(ghostdag_data);
+        match self
+            .ghostdag_store
+            .insert(header.hash(), ghostdag_data.clone())
+        {
+            Ok(()) | Err(StoreError::KeyAlreadyExists(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.ghostdag_cache.insert(header.hash(), ghostdag_data);
 
         // store relations
-        self.relations_store
+        match self
+            .relations_store
             .insert(header.hash(), BlockHashes::new(parents_hash.to_vec()))
-            .unwrap();
-        // Store header store
+        {
+            Ok(()) | Err(StoreError::KeyAlreadyExists(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        // Store header store last: its presence is what marks this header as fully committed.
         self.header_store
-            .insert(header.hash(), Arc::new(header.to_owned()), 0)
-            .unwrap();
+            .insert(header.hash(), Arc::new(header.to_owned()), 0)?;
+        self.in_dag_cache.insert(header.hash(), true);
+
+        self.update_tips(header)?;
+        self.emit_to_sync_accumulator(header.hash())?;
+        self.apply_pruning(header.hash())?;
+
+        Ok(())
+    }
+
+    /// Reset the reachability reindex root to `origin`, used to recover from a
+    /// `ReachabilityError::DataInconsistency` without losing the rest of the DAG state.
+    fn set_reindex_root(&mut self, origin: Hash) -> anyhow::Result<()> {
+        ensure!(
+            origin == Hash::new(ORIGIN),
+            "reindex root reset is only supported against the DAG origin, got {}",
+            origin
+        );
+        let mut reachability_store = self.reachability_store.clone();
+        inquirer::init(&mut reachability_store)?;
+        self.reachability_store = reachability_store;
+        Ok(())
+    }
+
+    /// `ghostdag_store.get`, going through `ghostdag_cache` first.
+    fn ghostdag_data(&self, hash: Hash) -> anyhow::Result<Option<Arc<GhostdagData>>> {
+        if let Some(cached) = self.ghostdag_cache.get(&hash) {
+            return Ok(Some(cached));
+        }
+        let Some(data) = self.ghostdag_store.get(hash)? else {
+            return Ok(None);
+        };
+        self.ghostdag_cache.insert(hash, data.clone());
+        Ok(Some(data))
+    }
+
+    /// Append `hash` to `sync_accumulator`, first recursively appending any of its
+    /// selected-parent/mergeset ancestors that aren't in it yet.
+    ///
+    /// The walk is keyed off each block's own immutable `ghostdag_data`: every committed block
+    /// gets appended (selected parent and merge set alike) exactly once, after its selected
+    /// parent and mergeset, and the recursion never revisits shared ancestry twice no matter
+    /// which sibling of a fork happens to commit first.
+    fn emit_to_sync_accumulator(&mut self, hash: Hash) -> anyhow::Result<()> {
+        if self.accumulator_emitted.contains(&hash) {
+            return Ok(());
+        }
+        // Genesis's own selected parent is the DAG origin sentinel, which never has ghostdag
+        // data of its own to recurse into.
+        let Some(data) = self.ghostdag_data(hash)? else {
+            return Ok(());
+        };
+        if data.selected_parent != hash {
+            self.emit_to_sync_accumulator(data.selected_parent)?;
+        }
+        let mut merge_set: Vec<Hash> = data.unordered_mergeset_without_selected_parent().collect();
+        // Sort so the order a multi-member merge set is appended in doesn't depend on whatever
+        // order the underlying `HashSet`/store iteration happened to produce.
+        merge_set.sort();
+        for merged in merge_set {
+            self.emit_to_sync_accumulator(merged)?;
+        }
+        if self.accumulator_emitted.insert(hash) {
+            self.sync_accumulator.append(hash)?;
+        }
+        Ok(())
+    }
+
+    /// Advance the pruning point from `tip` and drop anything it just pruned from our own
+    /// read caches, so a stale hit can't paper over data `PruningPointManager` has deleted.
+    fn apply_pruning(&mut self, tip: Hash) -> anyhow::Result<()> {
+        self.pruning_manager.update_pruning_point(tip)?;
+        let recently_pruned = self.pruning_manager.drain_recently_pruned();
+        for pruned in &recently_pruned {
+            self.ghostdag_cache.invalidate(pruned);
+            self.in_dag_cache.invalidate(pruned);
+        }
+        if !recently_pruned.is_empty() {
+            // `ancestor_cache` is keyed `(block, tip)`, not by a single hash, so there's no
+            // cheap way to enumerate just the entries a pruned hash appears in; clear the whole
+            // cache instead. Pruning is rare relative to `check_ancestor_of` calls, and a pruned
+            // block can never again be a reachable ancestor of anything (its reachability data
+            // is deleted), so this is a better trade than leaving stale `true` answers cached
+            // indefinitely.
+            self.ancestor_cache.clear();
+        }
+        Ok(())
+    }
+    /// Commit a header together with GHOSTDAG data that was already computed by a trusted
+    /// peer, instead of recomputing it locally via `ghostdag_manager.ghostdag`. This is the
+    /// fast path used when syncing historical blocks: the consensus ordering for the block is
+    /// known ahead of time, so instead of a full `ghostdag()` recompute we cross-check the two
+    /// properties a malicious or buggy peer could abuse — which parent the trusted data claims
+    /// as selected, and that every mergeset member is actually reachable from a parent — against
+    /// data we already have on disk.
+    ///
+    /// Mirrors `commit_header`'s store-write order and error handling (reachability first,
+    /// `header_store` last as the "fully committed" marker) so a retried/resumed trusted-sync
+    /// commit is just as safe to replay as an ordinary one.
+    pub fn commit_trusted_block(
+        &mut self,
+        header: &Header,
+        trusted_ghostdag: Arc<GhostdagData>,
+    ) -> anyhow::Result<()> {
+        if self.header_store.has(header.hash())? {
+            return Ok(());
+        }
+
+        let parents_hash = header.parents_hash();
+
+        // The GHOSTDAG selected parent is always the parent with the highest blue work, which
+        // we already have on disk for every parent (they must already be committed). Checking
+        // that is O(|parents|) store reads instead of a full `ghostdag()` recompute, and is
+        // enough to catch a trusted peer lying about the selected parent.
+        let mut parent_ghostdag = Vec::with_capacity(parents_hash.len());
+        for parent in parents_hash {
+            let parent_data = self
+                .ghostdag_data(*parent)?
+                .ok_or_else(|| anyhow::anyhow!("missing ghostdag data for parent {}", parent))?;
+            parent_ghostdag.push((*parent, parent_data));
+        }
+        let expected_selected_parent = parent_ghostdag
+            .iter()
+            .max_by_key(|(_, data)| data.blue_work)
+            .map(|(parent, _)| *parent);
+        ensure!(
+            expected_selected_parent == Some(trusted_ghostdag.selected_parent),
+            "trusted ghostdag data for {} disagrees on selected parent: expected {:?}, trusted {}",
+            header.hash(),
+            expected_selected_parent,
+            trusted_ghostdag.selected_parent
+        );
+
+        // The mergeset isn't free-form either: every block in it must already be part of the
+        // local history a parent is itself vouching for (the parent itself, its own selected
+        // parent, or its own mergeset) — a legitimate GHOSTDAG mergeset can only pull in blocks
+        // reachable from a parent, never blocks a trusted peer invented out of thin air. This
+        // doesn't re-derive the *correct* mergeset (that would defeat the point of trusting a
+        // peer for it), but it bounds what the peer can get away with smuggling into storage as
+        // this block's blue set.
+        let mut known_candidates: HashSet<Hash> = HashSet::new();
+        for (parent, parent_data) in &parent_ghostdag {
+            known_candidates.insert(*parent);
+            known_candidates.insert(parent_data.selected_parent);
+            known_candidates.extend(parent_data.unordered_mergeset_without_selected_parent());
+        }
+        for merged in trusted_ghostdag.unordered_mergeset_without_selected_parent() {
+            ensure!(
+                known_candidates.contains(&merged),
+                "trusted ghostdag data for {} claims mergeset member {} that isn't reachable \
+                 from any parent's own recorded history",
+                header.hash(),
+                merged
+            );
+        }
+
+        // Update reachability store first, exactly as commit_header does.
+        let mut reachability_store = self.reachability_store.clone();
+        let mut merge_set = trusted_ghostdag
+            .unordered_mergeset_without_selected_parent()
+            .filter(|hash| self.reachability_store.has(*hash).unwrap());
+
+        match inquirer::add_block(
+            &mut reachability_store,
+            header.hash(),
+            trusted_ghostdag.selected_parent,
+            &mut merge_set,
+        ) {
+            Ok(()) => {}
+            Err(ReachabilityError::DataInconsistency) => {
+                self.set_reindex_root(Hash::new(ORIGIN))?;
+                bail!(
+                    "reachability data inconsistency while committing trusted block {}, reindex root was reset",
+                    header.hash()
+                );
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        // Store the trusted data directly, skipping the (already verified) local computation.
+        match self
+            .ghostdag_store
+            .insert(header.hash(), trusted_ghostdag.clone())
+        {
+            Ok(()) | Err(StoreError::KeyAlreadyExists(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.ghostdag_cache.insert(header.hash(), trusted_ghostdag);
+
+        // store relations
+        match self
+            .relations_store
+            .insert(header.hash(), BlockHashes::new(parents_hash.to_vec()))
+        {
+            Ok(()) | Err(StoreError::KeyAlreadyExists(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        // Store header store last: its presence is what marks this header as fully committed.
+        self.header_store
+            .insert(header.hash(), Arc::new(header.to_owned()), 0)?;
+        self.in_dag_cache.insert(header.hash(), true);
+
+        self.update_tips(header)?;
+        self.emit_to_sync_accumulator(header.hash())?;
+        self.apply_pruning(header.hash())?;
+
+        Ok(())
+    }
+
+    /// The current root of the DAG sync accumulator, i.e. a commitment over every block
+    /// committed so far, in GHOSTDAG topological order.
+    pub fn get_accumulator_root(&self) -> Hash {
+        self.sync_accumulator.root()
+    }
+
+    /// A contiguous, verifiable slice of DAG history: the `count` blocks starting at leaf
+    /// `start`, in the order they were committed. Used to serve a syncing peer a range it can
+    /// verify against `get_accumulator_root` before calling `commit_trusted_block` on each one.
+    pub fn get_leaf_range(&self, start: u64, count: u64) -> anyhow::Result<Vec<Hash>> {
+        self.sync_accumulator.get_leaf_range(start, count)
+    }
+
+    /// Recompute the tip set after `header` has been committed: its parents are no longer
+    /// leaves, and `header` itself becomes a new tip unless it is already an ancestor of one
+    /// of the remaining tips (which can happen when blocks are committed out of arrival order,
+    /// e.g. while draining resolved orphans).
+    fn update_tips(&mut self, header: &Header) -> anyhow::Result<()> {
+        for parent in header.parents_hash() {
+            self.tips.remove(parent);
+        }
+        let existing_tips: Vec<Hash> = self.tips.iter().cloned().collect();
+        if !self.check_ancestor_of(header.hash(), existing_tips)? {
+            self.tips.insert(header.hash());
+        }
+        Ok(())
+    }
+
+    /// The current leaf set of the DAG: headers that are not an ancestor of any other known
+    /// header.
+    pub fn get_tips(&self) -> Vec<Hash> {
+        self.tips.iter().cloned().collect()
     }
+
+    /// Whether `hash` is a known header in the DAG.
     fn is_in_dag(&self, hash: Hash) -> anyhow::Result<bool> {
-        return Ok(true);
+        if let Some(cached) = self.in_dag_cache.get(&hash) {
+            return Ok(cached);
+        }
+        let in_dag = self.header_store.has(hash)?;
+        self.in_dag_cache.insert(hash, in_dag);
+        Ok(in_dag)
+    }
+
+    /// Whether `block` is a DAG ancestor of any of `tips`, using the reachability index.
+    /// `reachability_service` re-walks the reindex tree on every call, so pairwise answers are
+    /// cached in `ancestor_cache`. Unlike `ghostdag_cache`/`in_dag_cache`, that cache isn't
+    /// invalidated per-hash when pruning runs — it's keyed `(block, tip)`, so a single pruned
+    /// hash can't be matched against cached entries without a full scan. `apply_pruning` clears
+    /// the whole cache instead whenever anything was pruned.
+    pub fn check_ancestor_of(&self, block: Hash, tips: Vec<Hash>) -> anyhow::Result<bool> {
+        for tip in tips {
+            if let Some(cached) = self.ancestor_cache.get(&(block, tip)) {
+                if cached {
+                    return Ok(true);
+                }
+                continue;
+            }
+            let is_ancestor = self.reachability_service.is_dag_ancestor_of(block, tip)?;
+            self.ancestor_cache.insert((block, tip), is_ancestor);
+            if is_ancestor {
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
+
     pub fn verify_header(&self, header: &Header) -> anyhow::Result<()> {
-        //TODO: implemented it
+        ensure!(
+            !header.parents_hash().is_empty(),
+            "header {} has no parents",
+            header.hash()
+        );
+        ensure!(
+            !self
+                .pruning_manager
+                .is_below_pruning_point(header.parents_hash())?,
+            "header {} attaches entirely below the pruning point and cannot be validated",
+            header.hash()
+        );
         Ok(())
     }
 
+    /// The current pruning point: the deepest block this node still considers final and keeps
+    /// full history for.
+    pub fn pruning_point(&self) -> Hash {
+        self.pruning_manager.pruning_point()
+    }
+
     pub fn connect_block(&mut self, header: &Header) -> anyhow::Result<()> {
         let _ = self.verify_header(header)?;
         let is_orphan_block = self.update_orphans(header)?;
         if is_orphan_block {
             return Ok(());
         }
-        self.commit_header(header);
+        self.commit_header(header)?;
         self.check_missing_block(header)?;
         Ok(())
     }
 
     pub fn check_missing_block(&mut self, header: &Header) -> anyhow::Result<()> {
-        if let Some(orphans) = self.missing_blocks.remove(&header.hash()) {
-            for orphan in orphans.iter() {
-                let is_orphan = self.is_orphan(&orphan)?;
-                if !is_orphan {
-                    self.commit_header(header);
-                }
+        self.drain_ready_blocks(header)
+    }
+
+    /// When `parent` has just been committed, pull every dependent buffered in the
+    /// absent-ancestor store that was only waiting on it and commit them in topological order
+    /// (a dependent is only committed once none of its parents are missing anymore, and
+    /// committing it can in turn unblock its own dependents via the recursive call below).
+    pub fn drain_ready_blocks(&mut self, parent: &Header) -> anyhow::Result<()> {
+        let Some(dependents) = self.absent_ancestor_store.get(parent.hash())? else {
+            return Ok(());
+        };
+        self.absent_ancestor_store.remove(parent.hash())?;
+
+        for dependent in dependents.as_ref().iter() {
+            if self.is_orphan(dependent)? {
+                // Still waiting on another parent; re-buffer under whichever one(s) remain.
+                self.update_orphans(dependent)?;
+                continue;
             }
+            self.commit_header(dependent)?;
+            self.drain_ready_blocks(dependent)?;
         }
         Ok(())
     }
+
+    /// Whether `header` still has at least one parent that is not yet committed to the DAG.
     fn is_orphan(&self, header: &Header) -> anyhow::Result<bool> {
         for parent in header.parents_hash() {
             if !self.is_in_dag(parent.to_owned())? {
-                return Ok(false);
+                return Ok(true);
             }
         }
-        return Ok(true);
+        Ok(false)
     }
 
     fn update_orphans(&mut self, block_header: &Header) -> anyhow::Result<bool> {
@@ -155,14 +594,8 @@ impl BlockDAG {
             if self.is_in_dag(parent.to_owned())? {
                 continue;
             }
-            if !self
-                .missing_blocks
-                .entry(parent.to_owned())
-                .or_insert_with(HashSet::new)
-                .insert(block_header.to_owned())
-            {
-                return Err(anyhow::anyhow!("Block already processed as a orphan"));
-            }
+            self.absent_ancestor_store
+                .insert(parent.to_owned(), block_header.to_owned())?;
             is_orphan = true;
         }
         Ok(is_orphan)
@@ -195,6 +628,153 @@ mod tests {
         let mut dag = BlockDAG::new(genesis, k, db);
 
         let block = Header::new(BlockHeader::random(), vec![genesis_hash]);
-        dag.commit_header(&block);
+        dag.commit_header(&block).unwrap();
+    }
+
+    /// Committing the same header twice must be a no-op the second time, not an error: the
+    /// `header_store`-keyed idempotency short-circuit in `commit_header` is what makes it safe
+    /// for a caller (e.g. `drain_ready_blocks` or a resumed sync) to retry a commit.
+    #[test]
+    fn commit_header_is_idempotent() {
+        let genesis = Header::new(BlockHeader::random(), vec![Hash::new(ORIGIN)]);
+        let genesis_hash = genesis.hash();
+        let k = 16;
+        let db_path = env::temp_dir().join("smolstc-commit-header-idempotent");
+        if db_path
+            .as_path()
+            .try_exists()
+            .unwrap_or_else(|_| panic!("Failed to check {db_path:?}"))
+        {
+            fs::remove_dir_all(db_path.as_path()).expect("Failed to delete temporary directory");
+        }
+        let config = FlexiDagStorageConfig::create_with_params(1, 0, 1024);
+        let db = FlexiDagStorage::create_from_path(db_path, config)
+            .expect("Failed to create flexidag storage");
+        let mut dag = BlockDAG::new(genesis, k, db);
+
+        let block = Header::new(BlockHeader::random(), vec![genesis_hash]);
+        dag.commit_header(&block).unwrap();
+        // Retry: must stay a no-op success instead of erroring or re-deriving state.
+        dag.commit_header(&block).unwrap();
+        assert!(dag.is_in_dag(block.hash()).unwrap());
+    }
+
+    /// A header whose parent simply hasn't arrived yet must be buffered as an orphan, not
+    /// rejected by `verify_header` as "below the pruning point" — those two cases look
+    /// identical via `header_store.has` alone, which is exactly the bug the `pruned` tombstone
+    /// set in `PruningPointManager` fixes.
+    #[test]
+    fn connect_block_buffers_and_drains_orphan() {
+        let genesis = Header::new(BlockHeader::random(), vec![Hash::new(ORIGIN)]);
+        let genesis_hash = genesis.hash();
+        let k = 16;
+        let db_path = env::temp_dir().join("smolstc-orphan-drain");
+        if db_path
+            .as_path()
+            .try_exists()
+            .unwrap_or_else(|_| panic!("Failed to check {db_path:?}"))
+        {
+            fs::remove_dir_all(db_path.as_path()).expect("Failed to delete temporary directory");
+        }
+        let config = FlexiDagStorageConfig::create_with_params(1, 0, 1024);
+        let db = FlexiDagStorage::create_from_path(db_path, config)
+            .expect("Failed to create flexidag storage");
+        let mut dag = BlockDAG::new(genesis, k, db);
+
+        let parent = Header::new(BlockHeader::random(), vec![genesis_hash]);
+        let child = Header::new(BlockHeader::random(), vec![parent.hash()]);
+
+        // `child` arrives before `parent`: it must be buffered as an orphan rather than
+        // rejected outright.
+        dag.connect_block(&child).unwrap();
+        assert!(!dag.is_in_dag(child.hash()).unwrap());
+
+        // Once `parent` commits, draining should pull `child` in behind it automatically.
+        dag.connect_block(&parent).unwrap();
+        assert!(dag.is_in_dag(parent.hash()).unwrap());
+        assert!(dag.is_in_dag(child.hash()).unwrap());
+    }
+
+    /// With a retention depth of 1, every commit should advance the pruning point by one block
+    /// and drop everything strictly below it — exercising `PruningPointManager`'s tombstone set
+    /// and `BlockDAG`'s cache invalidation together, since `is_in_dag` only sees the dropped
+    /// state if `apply_pruning` actually invalidated `in_dag_cache` for it.
+    #[test]
+    fn pruning_drops_old_blocks_and_rejects_attaching_below_it() {
+        let genesis = Header::new(BlockHeader::random(), vec![Hash::new(ORIGIN)]);
+        let genesis_hash = genesis.hash();
+        let k = 16;
+        let db_path = env::temp_dir().join("smolstc-pruning-drops-old-blocks");
+        if db_path
+            .as_path()
+            .try_exists()
+            .unwrap_or_else(|_| panic!("Failed to check {db_path:?}"))
+        {
+            fs::remove_dir_all(db_path.as_path()).expect("Failed to delete temporary directory");
+        }
+        let config = FlexiDagStorageConfig::create_with_params(1, 0, 1024);
+        let db = FlexiDagStorage::create_from_path(db_path, config)
+            .expect("Failed to create flexidag storage");
+        let mut dag = BlockDAG::new_with_pruning_depth(genesis, k, db, 1);
+
+        let b1 = Header::new(BlockHeader::random(), vec![genesis_hash]);
+        dag.commit_header(&b1).unwrap();
+        let b2 = Header::new(BlockHeader::random(), vec![b1.hash()]);
+        dag.commit_header(&b2).unwrap();
+        let b3 = Header::new(BlockHeader::random(), vec![b2.hash()]);
+        dag.commit_header(&b3).unwrap();
+
+        assert_eq!(dag.pruning_point(), b2.hash());
+        assert!(!dag.is_in_dag(genesis_hash).unwrap());
+        assert!(!dag.is_in_dag(b1.hash()).unwrap());
+        assert!(dag.is_in_dag(b2.hash()).unwrap());
+        assert!(dag.is_in_dag(b3.hash()).unwrap());
+
+        // A header attaching to the now-pruned genesis can no longer be validated.
+        let attaches_below_pruning_point =
+            Header::new(BlockHeader::random(), vec![genesis_hash]);
+        assert!(dag.verify_header(&attaches_below_pruning_point).is_err());
+    }
+
+    /// Two children of genesis is the simplest possible DAG fork. `emit_to_sync_accumulator`
+    /// must append every block exactly once (genesis plus both siblings, not just one
+    /// selected-parent backbone), and the resulting root must not depend on which sibling
+    /// happened to commit first.
+    #[test]
+    fn sync_accumulator_covers_fork_deterministically() {
+        let genesis = Header::new(BlockHeader::random(), vec![Hash::new(ORIGIN)]);
+        let genesis_hash = genesis.hash();
+        let k = 16;
+        let sibling_a = Header::new(BlockHeader::random(), vec![genesis_hash]);
+        let sibling_b = Header::new(BlockHeader::random(), vec![genesis_hash]);
+
+        let db_path_ab = env::temp_dir().join("smolstc-fork-ab");
+        if db_path_ab.as_path().try_exists().unwrap_or(false) {
+            fs::remove_dir_all(db_path_ab.as_path()).expect("Failed to delete temporary directory");
+        }
+        let config_ab = FlexiDagStorageConfig::create_with_params(1, 0, 1024);
+        let db_ab = FlexiDagStorage::create_from_path(db_path_ab, config_ab)
+            .expect("Failed to create flexidag storage");
+        let mut dag_ab = BlockDAG::new(genesis.clone(), k, db_ab);
+        dag_ab.commit_header(&sibling_a).unwrap();
+        dag_ab.commit_header(&sibling_b).unwrap();
+
+        let db_path_ba = env::temp_dir().join("smolstc-fork-ba");
+        if db_path_ba.as_path().try_exists().unwrap_or(false) {
+            fs::remove_dir_all(db_path_ba.as_path()).expect("Failed to delete temporary directory");
+        }
+        let config_ba = FlexiDagStorageConfig::create_with_params(1, 0, 1024);
+        let db_ba = FlexiDagStorage::create_from_path(db_path_ba, config_ba)
+            .expect("Failed to create flexidag storage");
+        let mut dag_ba = BlockDAG::new(genesis, k, db_ba);
+        dag_ba.commit_header(&sibling_b).unwrap();
+        dag_ba.commit_header(&sibling_a).unwrap();
+
+        // Every block committed (genesis + both siblings) must have an accumulator leaf, with
+        // no duplicates from re-walking shared ancestry.
+        assert_eq!(dag_ab.sync_accumulator.num_leaves(), 3);
+        assert_eq!(dag_ba.sync_accumulator.num_leaves(), 3);
+        // The root must be identical regardless of which sibling committed first.
+        assert_eq!(dag_ab.get_accumulator_root(), dag_ba.get_accumulator_root());
     }
 }
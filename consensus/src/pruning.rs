@@ -0,0 +1,177 @@
+use consensus_types::blockhash::BlockHashes;
+use database::cache::{CacheCapacities, LruCache};
+use database::consensus::{
+    DbGhostdagStore, DbHeadersStore, DbReachabilityStore, DbRelationsStore, GhostdagStore,
+    HeaderStore, ReachabilityStore, RelationsStore, RelationsStoreReader,
+};
+use ghostdag::types::GhostdagData;
+use reachability::inquirer;
+use starcoin_crypto::HashValue as Hash;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Tracks and enforces a moving pruning point: the deepest block still considered final.
+/// Once a tip's selected-parent chain advances `retention_depth` blocks past the current
+/// pruning point, everything strictly below the new point is deleted from the
+/// ghostdag/header/relations stores and the reachability reindex root is rebased onto it, so
+/// long-running nodes keep bounded disk usage instead of retaining the full DAG history
+/// forever.
+pub struct PruningPointManager {
+    ghostdag_store: DbGhostdagStore,
+    header_store: DbHeadersStore,
+    relations_store: DbRelationsStore,
+    reachability_store: DbReachabilityStore,
+    retention_depth: u64,
+    pruning_point: Hash,
+    /// Hashes of every block pruned so far. `header_store` absence alone can't tell a pruned
+    /// block apart from a header that simply hasn't arrived yet (an ordinary orphan parent),
+    /// since `prune_below` deletes the header entirely; this tombstone set is the actual
+    /// "pruned" marker `is_below_pruning_point` needs to make that distinction.
+    pruned: HashSet<Hash>,
+    /// Hashes pruned since the last `drain_recently_pruned` call, so `BlockDAG` can invalidate
+    /// its own `header_store`/`ancestor` read caches instead of serving stale hits for blocks
+    /// this manager has since deleted.
+    recently_pruned: Vec<Hash>,
+    /// Cache in front of `ghostdag_store.get`. This is the *same* `LruCache` instance `BlockDAG`
+    /// uses for its own `ghostdag_store` reads (shared via `Arc`, constructed once in
+    /// `BlockDAG::new_with_config`) rather than a second independent cache over the same key
+    /// space: `commit_header` and `update_pruning_point`'s selected-parent walk both query the
+    /// same hashes, so sharing means a hash populated by one is already warm for the other.
+    ghostdag_cache: Arc<LruCache<Hash, Arc<GhostdagData>>>,
+    /// Cache in front of `relations_store.get_parents`, which `prune_below`'s BFS over
+    /// ancestors of the new pruning point re-walks on every call. Unlike `ghostdag_cache`, this
+    /// one isn't shared with `BlockDAG`: `relations_store` reads only get hot here, inside the
+    /// pruning walk.
+    relations_cache: LruCache<Hash, Arc<BlockHashes>>,
+}
+
+impl PruningPointManager {
+    pub fn new(
+        ghostdag_store: DbGhostdagStore,
+        header_store: DbHeadersStore,
+        relations_store: DbRelationsStore,
+        reachability_store: DbReachabilityStore,
+        origin: Hash,
+        retention_depth: u64,
+        cache_capacities: CacheCapacities,
+        ghostdag_cache: Arc<LruCache<Hash, Arc<GhostdagData>>>,
+    ) -> Self {
+        Self {
+            ghostdag_store,
+            header_store,
+            relations_store,
+            reachability_store,
+            retention_depth,
+            pruning_point: origin,
+            pruned: HashSet::new(),
+            recently_pruned: Vec::new(),
+            ghostdag_cache,
+            relations_cache: LruCache::new(cache_capacities.relations),
+        }
+    }
+
+    pub fn pruning_point(&self) -> Hash {
+        self.pruning_point
+    }
+
+    /// Drain and return the hashes pruned since the last call, for the caller to invalidate any
+    /// read caches of its own that might still hold them.
+    pub fn drain_recently_pruned(&mut self) -> Vec<Hash> {
+        std::mem::take(&mut self.recently_pruned)
+    }
+
+    /// `ghostdag_store.get`, going through the shared `ghostdag_cache` first.
+    fn ghostdag_data(&self, hash: Hash) -> anyhow::Result<Option<Arc<GhostdagData>>> {
+        if let Some(cached) = self.ghostdag_cache.get(&hash) {
+            return Ok(Some(cached));
+        }
+        let Some(data) = self.ghostdag_store.get(hash)? else {
+            return Ok(None);
+        };
+        self.ghostdag_cache.insert(hash, data.clone());
+        Ok(Some(data))
+    }
+
+    /// `relations_store.get_parents`, going through `relations_cache` first.
+    fn get_parents(&self, hash: Hash) -> anyhow::Result<Arc<BlockHashes>> {
+        if let Some(cached) = self.relations_cache.get(&hash) {
+            return Ok(cached);
+        }
+        let parents = self.relations_store.get_parents(hash)?;
+        self.relations_cache.insert(hash, parents.clone());
+        Ok(parents)
+    }
+
+    /// Whether every one of `parents` is already below the pruning point, i.e. the header
+    /// they belong to is attaching to history this node has already discarded and can no
+    /// longer validate. Only the `pruned` tombstone set counts as "discarded" here — a parent
+    /// that is merely absent from `header_store` (not yet seen, the common orphan case) must
+    /// not be treated as pruned, or orphan buffering breaks for the normal "parent arrives
+    /// after child" case.
+    pub fn is_below_pruning_point(&self, parents: &[Hash]) -> anyhow::Result<bool> {
+        if parents.is_empty() {
+            return Ok(false);
+        }
+        for parent in parents {
+            if !self.pruned.contains(parent) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Walk the selected-parent chain back from `tip` by `retention_depth` blocks and, if that
+    /// advances the pruning point, prune everything below the new point.
+    pub fn update_pruning_point(&mut self, tip: Hash) -> anyhow::Result<Hash> {
+        let mut candidate = tip;
+        for _ in 0..self.retention_depth {
+            let Some(data) = self.ghostdag_data(candidate)? else {
+                break;
+            };
+            if data.selected_parent == candidate {
+                break;
+            }
+            candidate = data.selected_parent;
+        }
+        if candidate != self.pruning_point {
+            self.prune_below(candidate)?;
+            self.pruning_point = candidate;
+        }
+        Ok(self.pruning_point)
+    }
+
+    /// Delete ghostdag/header/relations entries for every block strictly below `point`, record
+    /// them as pruned (see `pruned` above), and rebase the reachability reindex root onto
+    /// `point` itself.
+    pub fn prune_below(&mut self, point: Hash) -> anyhow::Result<()> {
+        let mut to_visit: Vec<Hash> = self
+            .get_parents(point)
+            .map(|parents| parents.as_ref().clone())
+            .unwrap_or_default();
+        let mut visited = HashSet::new();
+        while let Some(hash) = to_visit.pop() {
+            if !visited.insert(hash) {
+                continue;
+            }
+            if let Ok(parents) = self.get_parents(hash) {
+                to_visit.extend(parents.iter().cloned());
+            }
+            let _ = self.ghostdag_store.remove(hash);
+            let _ = self.header_store.remove(hash);
+            let _ = self.relations_store.remove(hash);
+            let _ = self.reachability_store.remove(hash);
+            self.ghostdag_cache.invalidate(&hash);
+            self.relations_cache.invalidate(&hash);
+            self.pruned.insert(hash);
+            self.recently_pruned.push(hash);
+        }
+
+        // Rebase onto `point` itself, not the DAG origin: unlike `BlockDAG::set_reindex_root`
+        // (which really does mean "reset to origin", for `DataInconsistency` recovery), pruning
+        // must keep reachability answers for every surviving block at or above `point` intact.
+        let mut reachability_store = self.reachability_store.clone();
+        inquirer::set_reindex_root(&mut reachability_store, point)?;
+        self.reachability_store = reachability_store;
+        Ok(())
+    }
+}
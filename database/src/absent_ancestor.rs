@@ -0,0 +1,156 @@
+use crate::db_access::ColumnFamilyAccess;
+use crate::define_schema;
+use crate::schema::{KeyCodec, ValueCodec};
+use anyhow::Result;
+use consensus_types::header::Header;
+use starcoin_crypto::HashValue as Hash;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+define_schema!(
+    AbsentAncestorSchema,
+    Hash,
+    HashSet<Header>,
+    "absent-ancestor"
+);
+
+impl KeyCodec<AbsentAncestorSchema> for Hash {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(Hash::from_slice(data)?)
+    }
+}
+
+impl ValueCodec<AbsentAncestorSchema> for HashSet<Header> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+pub trait AbsentAncestorStoreReader {
+    /// The headers still waiting on `missing_parent` to be committed, if any.
+    fn get(&self, missing_parent: Hash) -> Result<Option<Arc<HashSet<Header>>>>;
+    fn has(&self, missing_parent: Hash) -> Result<bool>;
+}
+
+pub trait AbsentAncestorStore: AbsentAncestorStoreReader {
+    /// Record `dependent` as waiting on `missing_parent`, merging with whatever is already
+    /// buffered for that parent.
+    fn insert(&self, missing_parent: Hash, dependent: Header) -> Result<()>;
+    /// Drop the buffered entry for `missing_parent`, e.g. once all its dependents have been
+    /// committed.
+    fn remove(&self, missing_parent: Hash) -> Result<()>;
+}
+
+/// RocksDB-backed store for orphan headers, keyed by the parent hash they are still waiting
+/// on. Unlike an in-memory `HashMap`, this survives a node restart mid-sync, so orphans don't
+/// have to be re-requested from peers after a crash.
+#[derive(Clone)]
+pub struct DbAbsentAncestorStore {
+    access: Arc<ColumnFamilyAccess<AbsentAncestorSchema>>,
+}
+
+impl DbAbsentAncestorStore {
+    pub fn new(db: Arc<rocksdb::DB>, cache_capacity: usize) -> Self {
+        Self {
+            access: Arc::new(ColumnFamilyAccess::new(db, cache_capacity)),
+        }
+    }
+}
+
+impl AbsentAncestorStoreReader for DbAbsentAncestorStore {
+    fn get(&self, missing_parent: Hash) -> Result<Option<Arc<HashSet<Header>>>> {
+        self.access.get(missing_parent)
+    }
+
+    fn has(&self, missing_parent: Hash) -> Result<bool> {
+        self.access.has(missing_parent)
+    }
+}
+
+impl AbsentAncestorStore for DbAbsentAncestorStore {
+    fn insert(&self, missing_parent: Hash, dependent: Header) -> Result<()> {
+        let mut dependents = self
+            .access
+            .get(missing_parent)?
+            .map(|existing| existing.as_ref().clone())
+            .unwrap_or_default();
+        dependents.insert(dependent);
+        self.access.insert(missing_parent, Arc::new(dependents))
+    }
+
+    fn remove(&self, missing_parent: Hash) -> Result<()> {
+        self.access.remove(missing_parent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starcoin_types::block::BlockHeader;
+    use std::env;
+    use std::fs;
+
+    /// A fresh header whose hash is only used as an arbitrary, distinct `Hash` value in these
+    /// tests — the parent list and header contents otherwise don't matter here.
+    fn random_hash() -> Hash {
+        Header::new(BlockHeader::random(), vec![]).hash()
+    }
+
+    fn open_store(dir_name: &str) -> DbAbsentAncestorStore {
+        let path = env::temp_dir().join(dir_name);
+        if path.as_path().try_exists().unwrap_or(false) {
+            fs::remove_dir_all(path.as_path()).expect("Failed to delete temporary directory");
+        }
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let db = rocksdb::DB::open_cf(
+            &options,
+            path.as_path(),
+            [AbsentAncestorSchema::COLUMN_FAMILY],
+        )
+        .expect("Failed to open temporary rocksdb");
+        DbAbsentAncestorStore::new(Arc::new(db), 8)
+    }
+
+    /// Inserting two dependents for the same missing parent must merge into one `HashSet`
+    /// entry, not overwrite the first with the second.
+    #[test]
+    fn insert_merges_dependents_for_the_same_missing_parent() {
+        let store = open_store("smolstc-absent-ancestor-merge");
+        let missing_parent = random_hash();
+        let first = Header::new(BlockHeader::random(), vec![missing_parent]);
+        let second = Header::new(BlockHeader::random(), vec![missing_parent]);
+
+        store.insert(missing_parent, first.clone()).unwrap();
+        store.insert(missing_parent, second.clone()).unwrap();
+
+        let dependents = store.get(missing_parent).unwrap().unwrap();
+        assert_eq!(dependents.len(), 2);
+        assert!(dependents.contains(&first));
+        assert!(dependents.contains(&second));
+    }
+
+    #[test]
+    fn has_and_remove_reflect_store_state() {
+        let store = open_store("smolstc-absent-ancestor-has-remove");
+        let missing_parent = random_hash();
+        assert!(!store.has(missing_parent).unwrap());
+
+        let dependent = Header::new(BlockHeader::random(), vec![missing_parent]);
+        store.insert(missing_parent, dependent).unwrap();
+        assert!(store.has(missing_parent).unwrap());
+
+        store.remove(missing_parent).unwrap();
+        assert!(!store.has(missing_parent).unwrap());
+        assert!(store.get(missing_parent).unwrap().is_none());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::schema::Schema;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Capacity knobs for the per-column-family read caches, passed to
+/// `BlockDAG::new_with_config` (`BlockDAG::new` uses `CacheCapacities::default()`);
+/// `FlexiDagStorageConfig` does not carry them. `reachability` sizes `BlockDAG`'s pairwise
+/// `check_ancestor_of` cache and gets the largest default budget.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheCapacities {
+    pub ghostdag: usize,
+    pub header: usize,
+    pub relations: usize,
+    pub reachability: usize,
+}
+
+impl Default for CacheCapacities {
+    fn default() -> Self {
+        Self {
+            ghostdag: 10_000,
+            header: 10_000,
+            relations: 10_000,
+            reachability: 50_000,
+        }
+    }
+}
+
+/// A bounded, in-memory LRU cache keyed on an arbitrary `K`. This is the mechanism underneath
+/// both `SchemaCache` (below, for column families we own the `Schema` for) and callers that
+/// front a store whose concrete type is opaque to them — e.g. `BlockDAG` caching
+/// `ghostdag_store`/`reachability_service` reads without access to their internal `Schema`
+/// types. Reads populate the cache on miss; writers must call `insert` (or `invalidate` for
+/// deletes) so the cache stays coherent with the underlying store.
+pub struct LruCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    capacity: usize,
+    state: Mutex<CacheState<K, V>>,
+}
+
+struct CacheState<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    map: HashMap<K, V>,
+    // Back = most recently used.
+    order: VecDeque<K>,
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState {
+                map: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Return the cached value for `key`, if present, promoting it to most-recently-used.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut state = self.state.lock();
+        let value = state.map.get(key).cloned();
+        if value.is_some() {
+            state.touch(key);
+        }
+        value
+    }
+
+    /// Insert (or refresh) a value, evicting the least recently used entry if we are over
+    /// capacity. A capacity of zero disables the cache.
+    pub fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        if state.map.insert(key.clone(), value).is_none() {
+            state.order.push_back(key);
+        } else {
+            state.touch(&key);
+        }
+        while state.map.len() > self.capacity {
+            let Some(oldest) = state.order.pop_front() else {
+                break;
+            };
+            state.map.remove(&oldest);
+        }
+    }
+
+    /// Drop `key` from the cache, e.g. when the underlying store entry was overwritten out of
+    /// band and the cached value can no longer be trusted.
+    pub fn invalidate(&self, key: &K) {
+        let mut state = self.state.lock();
+        state.map.remove(key);
+        state.order.retain(|k| k != key);
+    }
+
+    /// Drop every entry. Used when an invalidation would otherwise have to enumerate keys that
+    /// are cheap to produce but expensive to match against every cached entry (e.g. pairwise
+    /// keys derived from a pruned hash) — clearing the whole cache is simpler and correct, at
+    /// the cost of the next round of lookups all missing.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.map.clear();
+        state.order.clear();
+    }
+}
+
+impl<K, V> CacheState<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    fn touch(&mut self, key: &K) {
+        self.order.retain(|k| k != key);
+        self.order.push_back(key.clone());
+    }
+}
+
+/// `LruCache` specialized for a `Schema`-keyed column family, sitting in front of RocksDB so
+/// repeat reads of the same key don't pay a decode cost.
+pub struct SchemaCache<S: Schema>
+where
+    S::Key: Eq + Hash,
+{
+    inner: LruCache<S::Key, Arc<S::Value>>,
+}
+
+impl<S: Schema> SchemaCache<S>
+where
+    S::Key: Eq + Hash,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: LruCache::new(capacity),
+        }
+    }
+
+    pub fn get(&self, key: &S::Key) -> Option<Arc<S::Value>> {
+        self.inner.get(key)
+    }
+
+    pub fn insert(&self, key: S::Key, value: Arc<S::Value>) {
+        self.inner.insert(key, value)
+    }
+
+    pub fn invalidate(&self, key: &S::Key) {
+        self.inner.invalidate(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_insert_invalidate_round_trip() {
+        let cache: LruCache<u64, &'static str> = LruCache::new(2);
+        assert_eq!(cache.get(&1), None);
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), Some("one"));
+        cache.invalidate(&1);
+        assert_eq!(cache.get(&1), None);
+    }
+
+    /// Inserting past `capacity` must evict the least recently used entry, not an arbitrary one.
+    #[test]
+    fn eviction_drops_least_recently_used() {
+        let cache: LruCache<u64, u64> = LruCache::new(2);
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        // Touch `1` so `2` becomes the least recently used entry.
+        assert_eq!(cache.get(&1), Some(1));
+        cache.insert(3, 3);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(1));
+        assert_eq!(cache.get(&3), Some(3));
+    }
+
+    /// A capacity of zero disables the cache outright rather than panicking or growing
+    /// unbounded.
+    #[test]
+    fn zero_capacity_never_caches() {
+        let cache: LruCache<u64, u64> = LruCache::new(0);
+        cache.insert(1, 1);
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn clear_drops_every_entry() {
+        let cache: LruCache<u64, u64> = LruCache::new(4);
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        cache.clear();
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), None);
+        // The cache must still be usable afterwards, not left in a broken state.
+        cache.insert(3, 3);
+        assert_eq!(cache.get(&3), Some(3));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::cache::SchemaCache;
+use crate::schema::{KeyCodec, Schema, ValueCodec};
+use anyhow::Result;
+use std::hash::Hash as StdHash;
+use std::sync::Arc;
+
+/// Generic RocksDB column-family access for a single `Schema`, with `SchemaCache` sitting in
+/// front of it so repeat reads of the same key don't pay a decode cost. This is the building
+/// block concrete stores (`DbAbsentAncestorStore`, the sync accumulator store, ...) are built
+/// on when they need their own column family rather than reusing one of the stores already
+/// wired into `FlexiDagStorage`.
+pub struct ColumnFamilyAccess<S: Schema>
+where
+    S::Key: Eq + StdHash,
+{
+    db: Arc<rocksdb::DB>,
+    cache: SchemaCache<S>,
+}
+
+impl<S: Schema> ColumnFamilyAccess<S>
+where
+    S::Key: Eq + StdHash,
+{
+    pub fn new(db: Arc<rocksdb::DB>, cache_capacity: usize) -> Self {
+        Self {
+            db,
+            cache: SchemaCache::new(cache_capacity),
+        }
+    }
+
+    pub fn has(&self, key: S::Key) -> Result<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    pub fn get(&self, key: S::Key) -> Result<Option<Arc<S::Value>>> {
+        if let Some(cached) = self.cache.get(&key) {
+            return Ok(Some(cached));
+        }
+        let cf = self.column_family();
+        let raw = self.db.get_cf(&cf, key.encode_key()?)?;
+        let Some(bytes) = raw else {
+            return Ok(None);
+        };
+        let value = Arc::new(S::Value::decode_value(&bytes)?);
+        self.cache.insert(key, value.clone());
+        Ok(Some(value))
+    }
+
+    pub fn insert(&self, key: S::Key, value: Arc<S::Value>) -> Result<()> {
+        let cf = self.column_family();
+        self.db
+            .put_cf(&cf, key.encode_key()?, value.encode_value()?)?;
+        self.cache.insert(key, value);
+        Ok(())
+    }
+
+    pub fn remove(&self, key: S::Key) -> Result<()> {
+        let cf = self.column_family();
+        self.db.delete_cf(&cf, key.encode_key()?)?;
+        self.cache.invalidate(&key);
+        Ok(())
+    }
+
+    fn column_family(&self) -> Arc<rocksdb::BoundColumnFamily> {
+        self.db
+            .cf_handle(S::COLUMN_FAMILY)
+            .unwrap_or_else(|| panic!("column family {} not opened", S::COLUMN_FAMILY))
+    }
+}
@@ -0,0 +1,110 @@
+use starcoin_crypto::HashValue as Hash;
+
+/// A minimal append-only Merkle Mountain Range: `append` folds equal-height peaks together
+/// (much like a binary counter carry) and `root` bags the remaining peaks right-to-left into a
+/// single digest. This is enough to produce a verifiable, tamper-evident ordering commitment
+/// for an append-only leaf sequence without needing inclusion-proof generation up front.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleAccumulator {
+    size: u64,
+    peaks: Vec<Hash>,
+}
+
+impl MerkleAccumulator {
+    pub fn new(size: u64, peaks: Vec<Hash>) -> Self {
+        Self { size, peaks }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn peaks(&self) -> &[Hash] {
+        &self.peaks
+    }
+
+    pub fn append(&mut self, leaf: Hash) {
+        let mut new_peak = leaf;
+        let mut height = 0u32;
+        while self.size & (1 << height) != 0 {
+            let left = self
+                .peaks
+                .pop()
+                .expect("a peak must exist for every set bit of size");
+            new_peak = hash_pair(left, new_peak);
+            height += 1;
+        }
+        self.peaks.push(new_peak);
+        self.size += 1;
+    }
+
+    pub fn root(&self) -> Hash {
+        let mut iter = self.peaks.iter().rev();
+        let Some(&first) = iter.next() else {
+            return Hash::zero();
+        };
+        iter.fold(first, |root, &peak| hash_pair(peak, root))
+    }
+}
+
+fn hash_pair(left: Hash, right: Hash) -> Hash {
+    let mut bytes = Vec::with_capacity(64);
+    bytes.extend_from_slice(left.as_ref());
+    bytes.extend_from_slice(right.as_ref());
+    Hash::sha3_256_of(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_accumulator_has_zero_root_and_size() {
+        let accumulator = MerkleAccumulator::default();
+        assert_eq!(accumulator.size(), 0);
+        assert_eq!(accumulator.root(), Hash::zero());
+    }
+
+    #[test]
+    fn append_increments_size_and_changes_root() {
+        let mut accumulator = MerkleAccumulator::default();
+        let empty_root = accumulator.root();
+        accumulator.append(Hash::sha3_256_of(b"leaf-0"));
+        assert_eq!(accumulator.size(), 1);
+        assert_ne!(accumulator.root(), empty_root);
+
+        let root_after_one = accumulator.root();
+        accumulator.append(Hash::sha3_256_of(b"leaf-1"));
+        assert_eq!(accumulator.size(), 2);
+        assert_ne!(accumulator.root(), root_after_one);
+    }
+
+    /// `new` from a live accumulator's `(size, peaks)` must reconstruct an identical accumulator,
+    /// since `DbSyncDagAccumulator::new` relies on exactly this to resume from persisted
+    /// `(size, peaks)` state instead of replaying every leaf.
+    #[test]
+    fn new_from_persisted_state_matches_live_accumulator() {
+        let mut live = MerkleAccumulator::default();
+        for i in 0..5u8 {
+            live.append(Hash::sha3_256_of(&[i]));
+        }
+        let restored = MerkleAccumulator::new(live.size(), live.peaks().to_vec());
+        assert_eq!(restored.size(), live.size());
+        assert_eq!(restored.root(), live.root());
+    }
+
+    /// The same leaves appended in the same order from two independent accumulators must
+    /// produce the same root — the whole point of using this as a tamper-evident commitment.
+    #[test]
+    fn same_leaves_same_order_produce_same_root() {
+        let leaves: Vec<Hash> = (0..7u8).map(|i| Hash::sha3_256_of(&[i])).collect();
+        let mut a = MerkleAccumulator::default();
+        let mut b = MerkleAccumulator::default();
+        for leaf in &leaves {
+            a.append(*leaf);
+            b.append(*leaf);
+        }
+        assert_eq!(a.root(), b.root());
+        assert_eq!(a.size(), leaves.len() as u64);
+    }
+}
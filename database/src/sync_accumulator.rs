@@ -0,0 +1,120 @@
+use crate::db_access::ColumnFamilyAccess;
+use crate::define_schema;
+use crate::merkle::MerkleAccumulator;
+use crate::schema::{KeyCodec, ValueCodec};
+use anyhow::Result;
+use parking_lot::RwLock;
+use starcoin_crypto::HashValue as Hash;
+use std::sync::Arc;
+
+define_schema!(SyncDagLeafSchema, u64, Hash, "sync-dag-leaf");
+define_schema!(SyncDagMetaSchema, u8, (u64, Vec<Hash>), "sync-dag-meta");
+
+/// The single row `SyncDagMetaSchema` is keyed under.
+const META_KEY: u8 = 0;
+
+impl KeyCodec<SyncDagLeafSchema> for u64 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        let bytes: [u8; 8] = data.try_into()?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+impl ValueCodec<SyncDagLeafSchema> for Hash {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(Hash::from_slice(data)?)
+    }
+}
+
+impl KeyCodec<SyncDagMetaSchema> for u8 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(vec![*self])
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(data[0])
+    }
+}
+
+impl ValueCodec<SyncDagMetaSchema> for (u64, Vec<Hash>) {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+/// Durable, append-only record of every block committed to the DAG — selected-parent chain and
+/// merged blocks alike, not just the backbone — in GHOSTDAG topological order: a block's
+/// selected parent and mergeset are always appended before the block itself. Backs the
+/// trusted-sync handshake: a serving node hands a peer a root (`root`) plus a leaf range
+/// (`get_leaf_range`), and the receiving node verifies the ordering before calling
+/// `BlockDAG::commit_trusted_block`. No inclusion proofs are generated for a range.
+#[derive(Clone)]
+pub struct DbSyncDagAccumulator {
+    leaves: Arc<ColumnFamilyAccess<SyncDagLeafSchema>>,
+    meta: Arc<ColumnFamilyAccess<SyncDagMetaSchema>>,
+    accumulator: Arc<RwLock<MerkleAccumulator>>,
+}
+
+impl DbSyncDagAccumulator {
+    pub fn new(db: Arc<rocksdb::DB>, cache_capacity: usize) -> Result<Self> {
+        let leaves = Arc::new(ColumnFamilyAccess::new(db.clone(), cache_capacity));
+        let meta = Arc::new(ColumnFamilyAccess::new(db, 1));
+        let (size, peaks) = meta
+            .get(META_KEY)?
+            .map(|state| state.as_ref().clone())
+            .unwrap_or_default();
+        Ok(Self {
+            leaves,
+            meta,
+            accumulator: Arc::new(RwLock::new(MerkleAccumulator::new(size, peaks))),
+        })
+    }
+
+    /// Append `block_id` as the next leaf. Callers are responsible for only calling this once a
+    /// block's selected parent and mergeset have already been appended (see
+    /// `BlockDAG::emit_to_sync_accumulator`), so leaf order is GHOSTDAG topological order.
+    pub fn append(&self, block_id: Hash) -> Result<()> {
+        let mut accumulator = self.accumulator.write();
+        let leaf_index = accumulator.size();
+        self.leaves.insert(leaf_index, Arc::new(block_id))?;
+        accumulator.append(block_id);
+        self.meta.insert(
+            META_KEY,
+            Arc::new((accumulator.size(), accumulator.peaks().to_vec())),
+        )?;
+        Ok(())
+    }
+
+    pub fn root(&self) -> Hash {
+        self.accumulator.read().root()
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.accumulator.read().size()
+    }
+
+    /// The `count` leaves starting at `start`, in GHOSTDAG topological order. The returned slice
+    /// is truncated if it would run past the current end of the accumulator.
+    pub fn get_leaf_range(&self, start: u64, count: u64) -> Result<Vec<Hash>> {
+        let end = start.saturating_add(count).min(self.num_leaves());
+        let mut leaves = Vec::with_capacity(end.saturating_sub(start) as usize);
+        for index in start..end {
+            if let Some(leaf) = self.leaves.get(index)? {
+                leaves.push(*leaf);
+            }
+        }
+        Ok(leaves)
+    }
+}